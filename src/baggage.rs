@@ -0,0 +1,45 @@
+//! W3C [Baggage](https://www.w3.org/TR/baggage/) propagation: carrying
+//! application-level key/value pairs (tenant id, request priority, ...)
+//! alongside the trace context.
+
+use opentelemetry::baggage::BaggageExt;
+use opentelemetry::propagation::TextMapPropagator;
+use opentelemetry_sdk::propagation::BaggagePropagator;
+
+/// The expected environment variable for carrying around W3C baggage among
+/// shell scripts, as a serialized `baggage` header value.
+pub static BAGGAGE: &str = "BAGGAGE";
+
+/// Parse the [`BAGGAGE`] environment variable (if set and valid) into the
+/// current [`opentelemetry::Context`]. Attach the result with
+/// [`opentelemetry::Context::attach`] to make it the active context.
+pub fn read_baggage() -> opentelemetry::Context {
+    let cx = opentelemetry::Context::current();
+    match std::env::var(BAGGAGE) {
+        Ok(val) => {
+            let mut carrier = std::collections::HashMap::new();
+            carrier.insert("baggage".to_string(), val);
+            BaggagePropagator::new().extract_with_context(&cx, &carrier)
+        }
+        Err(_) => cx,
+    }
+}
+
+/// Set a baggage entry on the current context, returning a guard that keeps
+/// it active until dropped.
+pub fn set_baggage(
+    key: impl Into<opentelemetry::Key>,
+    value: impl Into<opentelemetry::Value>,
+) -> opentelemetry::ContextGuard {
+    opentelemetry::Context::current()
+        .with_baggage(vec![opentelemetry::KeyValue::new(key, value)])
+        .attach()
+}
+
+/// Look up a baggage entry on the current context.
+pub fn get_baggage(key: impl Into<opentelemetry::Key>) -> Option<String> {
+    opentelemetry::Context::current()
+        .baggage()
+        .get(key)
+        .map(|v| v.to_string())
+}
@@ -0,0 +1,129 @@
+//! Selectable span exporter backends, so local development and tests don't
+//! need a running OTLP collector just to exercise span/traceparent handling.
+
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_sdk::export::trace::{ExportResult, SpanData, SpanExporter};
+use opentelemetry_sdk::trace::{Tracer, TracerProvider};
+
+/// The environment variable used to select a [`CollectorKind`] in [`crate::init`],
+/// instead of always standing up the OTLP exporter.
+pub static OTEL_COLLECTOR_KIND: &str = "OTEL_COLLECTOR_KIND";
+
+/// Which span exporter backend [`crate::init`]/[`crate::init_with_kind`]
+/// should stand up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CollectorKind {
+    /// Export to a real OTLP collector, per the OTLP exporter environment
+    /// variables. The existing, default behavior.
+    #[default]
+    Otlp,
+    /// Pretty-print finished spans to stdout. Handy for local development
+    /// without a collector running.
+    Stdout,
+    /// Pretty-print finished spans to stderr.
+    Stderr,
+    /// Build a real tracer, but discard every exported span. Gives
+    /// deterministic, collector-free tests while span context and
+    /// [`crate::generate_traceparent`] keep working.
+    NoWrite,
+}
+
+impl std::str::FromStr for CollectorKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "otlp" => Ok(CollectorKind::Otlp),
+            "stdout" => Ok(CollectorKind::Stdout),
+            "stderr" => Ok(CollectorKind::Stderr),
+            "nowrite" | "noop" | "none" => Ok(CollectorKind::NoWrite),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Read [`OTEL_COLLECTOR_KIND`] and parse it into a [`CollectorKind`],
+/// defaulting to [`CollectorKind::Otlp`] if it's absent or unrecognized.
+pub fn collector_kind_from_env() -> CollectorKind {
+    std::env::var(OTEL_COLLECTOR_KIND)
+        .ok()
+        .and_then(|val| val.parse().ok())
+        .unwrap_or_default()
+}
+
+/// A [`SpanExporter`] that discards every span it's handed. Still backed by
+/// a real [`TracerProvider`], so span ids, sampling, and traceparent
+/// generation all keep working as normal; only the export itself is a
+/// no-op.
+#[derive(Debug, Default)]
+struct NoWriteExporter;
+
+impl SpanExporter for NoWriteExporter {
+    fn export(
+        &mut self,
+        _batch: Vec<SpanData>,
+    ) -> futures_util::future::BoxFuture<'static, ExportResult> {
+        Box::pin(async move { Ok(()) })
+    }
+}
+
+/// Build a [`Tracer`] backed by the given non-OTLP [`CollectorKind`].
+///
+/// Given [`CollectorKind::Otlp`], this builds a provider with no exporter at
+/// all; [`crate::init_with_kind`] never passes that kind here and uses
+/// `init-tracing-opentelemetry`'s own OTLP setup for it instead.
+pub(crate) fn tracer_for_kind(name: &'static str, kind: CollectorKind) -> Tracer {
+    let provider = match kind {
+        CollectorKind::Otlp => TracerProvider::builder().build(),
+        CollectorKind::Stdout => TracerProvider::builder()
+            .with_simple_exporter(opentelemetry_stdout::SpanExporter::default())
+            .build(),
+        CollectorKind::Stderr => TracerProvider::builder()
+            .with_simple_exporter(
+                opentelemetry_stdout::SpanExporter::builder()
+                    .with_writer(std::io::stderr())
+                    .build(),
+            )
+            .build(),
+        CollectorKind::NoWrite => TracerProvider::builder()
+            .with_simple_exporter(NoWriteExporter::default())
+            .build(),
+    };
+    let tracer = provider.tracer(name);
+    opentelemetry::global::set_tracer_provider(provider);
+    tracer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collector_kind_from_str_recognizes_all_variants() {
+        assert_eq!("otlp".parse(), Ok(CollectorKind::Otlp));
+        assert_eq!("stdout".parse(), Ok(CollectorKind::Stdout));
+        assert_eq!("STDERR".parse(), Ok(CollectorKind::Stderr));
+        assert_eq!("nowrite".parse(), Ok(CollectorKind::NoWrite));
+        assert_eq!("noop".parse(), Ok(CollectorKind::NoWrite));
+        assert_eq!("bogus".parse::<CollectorKind>(), Err(()));
+    }
+
+    #[test]
+    fn collector_kind_from_env_defaults_to_otlp_when_unset() {
+        std::env::remove_var(OTEL_COLLECTOR_KIND);
+        assert_eq!(collector_kind_from_env(), CollectorKind::Otlp);
+    }
+
+    #[test]
+    fn collector_kind_from_env_reads_the_variable() {
+        std::env::set_var(OTEL_COLLECTOR_KIND, "stdout");
+        assert_eq!(collector_kind_from_env(), CollectorKind::Stdout);
+        std::env::remove_var(OTEL_COLLECTOR_KIND);
+    }
+
+    #[tokio::test]
+    async fn no_write_exporter_always_succeeds() {
+        let mut exporter = NoWriteExporter;
+        assert!(exporter.export(vec![]).await.is_ok());
+    }
+}
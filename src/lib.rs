@@ -31,13 +31,44 @@
 //! }
 //! ```
 
-use opentelemetry::trace::{Span, Tracer};
+use opentelemetry::trace::Tracer;
 use tracing_subscriber::prelude::__tracing_subscriber_SubscriberExt;
 
+mod baggage;
+mod exporter;
+mod propagation;
+pub use baggage::{get_baggage, read_baggage, set_baggage, BAGGAGE};
+pub use exporter::{CollectorKind, OTEL_COLLECTOR_KIND};
+pub use propagation::{extract_context, inject_context, PropagationFormat, OTEL_PROPAGATORS};
+
 /// The expected environment variable for carrying around W3C traceparents
 /// among shell scripts
 pub static TRACEPARENT: &str = "TRACEPARENT";
 
+/// The expected environment variable for carrying around a W3C tracestate
+/// among shell scripts, as a comma-separated list of `key=value` pairs
+/// per <https://www.w3.org/TR/trace-context/#tracestate-header>
+pub static TRACESTATE: &str = "TRACESTATE";
+
+/// Attempt to parse a [`opentelemetry::trace::TraceState`] from the
+/// [`TRACESTATE`] environment variable. Entries without an `=` are skipped.
+/// Falls back to an empty tracestate if the variable is absent or the
+/// remaining pairs do not form a valid tracestate.
+pub fn read_tracestate() -> opentelemetry::trace::TraceState {
+    match std::env::var(TRACESTATE) {
+        Ok(val) => {
+            let pairs = val
+                .split(',')
+                .filter_map(|pair| pair.split_once('='))
+                .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+                .collect::<Vec<_>>();
+            opentelemetry::trace::TraceState::from_key_value(pairs)
+                .unwrap_or(opentelemetry::trace::TraceState::NONE)
+        }
+        Err(_) => opentelemetry::trace::TraceState::NONE,
+    }
+}
+
 /// Attempt to parse a valid traceparent from the [`TRACEPARENT`] environment
 /// variable
 pub fn read_traceparent() -> Option<traceparent::Traceparent> {
@@ -78,9 +109,9 @@ impl ToSpanContext for traceparent::Traceparent {
         opentelemetry::trace::SpanContext::new(
             self.trace_id().into(),
             self.parent_id().into(),
-            opentelemetry::trace::TraceFlags::SAMPLED, // self.flags().into(),
-            false,                                     // TODO: should this be something else?
-            opentelemetry::trace::TraceState::NONE,
+            opentelemetry::trace::TraceFlags::new(self.flags()),
+            false, // TODO: should this be something else?
+            read_tracestate(),
         )
     }
 }
@@ -109,21 +140,65 @@ pub fn start_with_traceparent(span_name: &'static str) -> opentelemetry::Context
 
 /// Start up a new otel span using name as the span name.
 /// If a valid [`TRACEPARENT`] environment variable is found it will be used
-/// to assemble span link that will be added to the new span.
+/// to assemble a span link — with no attributes — that will be added to the
+/// new span. See [`start_with_spanlink_attrs`] to attach attributes
+/// explaining why the link exists.
 pub fn start_with_spanlink(span_name: &'static str) -> opentelemetry::ContextGuard {
+    start_with_spanlink_attrs(span_name, Vec::new())
+}
+
+/// Start up a new otel span using name as the span name.
+/// If a valid [`TRACEPARENT`] environment variable is found it will be used
+/// to assemble a span link, carrying `attrs`, that will be added to the new
+/// span — e.g. `link.type=follows_from`, a batch id, or the originating
+/// shell command, explaining why the link exists.
+///
+/// The link is attached at creation time: this builds the span through a
+/// [`opentelemetry::trace::SpanBuilder`] rather than `Tracer::start`, setting
+/// its `links` field.
+pub fn start_with_spanlink_attrs(
+    span_name: &'static str,
+    attrs: impl IntoIterator<Item = opentelemetry::KeyValue>,
+) -> opentelemetry::ContextGuard {
     // The use of empty string here will cause you to get a tracer named the same as what you
     // provided to our init function.
     let tracer = opentelemetry::global::tracer("");
-    let mut span = tracer.start(span_name);
+    let mut builder = tracer.span_builder(span_name);
     if let Some(tp) = read_traceparent() {
-        span.add_link(
-            tp.as_spancontext(),
-            vec![opentelemetry::KeyValue {
-                key: "key".into(), // TODO: something useful here?
-                value: "value".into(),
-            }],
-        );
-    };
+        builder.links = Some(vec![span_link(tp.as_spancontext(), attrs)]);
+    }
+    let span = builder.start(&tracer);
+    opentelemetry::trace::mark_span_as_active(span)
+}
+
+/// Build a [`opentelemetry::trace::Link`] to `span_context` carrying
+/// `attrs`, for passing into a [`opentelemetry::trace::SpanBuilder`]'s
+/// `links` field when creating a new span (see [`start_with_spanlink_attrs`]).
+///
+/// This crate has no helper for adding a link to a span that is already
+/// active; links built here are attached when a span is created.
+pub fn span_link(
+    span_context: opentelemetry::trace::SpanContext,
+    attrs: impl IntoIterator<Item = opentelemetry::KeyValue>,
+) -> opentelemetry::trace::Link {
+    opentelemetry::trace::Link::new(span_context, attrs.into_iter().collect())
+}
+
+/// Start up a new otel span using name as the span name, parented off the
+/// given [`opentelemetry::Context`].
+///
+/// Unlike [`start_with_traceparent`], which only looks at the
+/// [`TRACEPARENT`] environment variable, this lets callers parent their span
+/// off a context pulled from an arbitrary carrier with
+/// [`extract_context`], e.g. the headers of an incoming axum/tonic request.
+pub fn start_with_context(
+    span_name: &'static str,
+    cx: &opentelemetry::Context,
+) -> opentelemetry::ContextGuard {
+    // The use of empty string here will cause you to get a tracer named the same as what you
+    // provided to our init function.
+    let tracer = opentelemetry::global::tracer("");
+    let span = tracer.start_with_context(span_name, cx);
     opentelemetry::trace::mark_span_as_active(span)
 }
 
@@ -146,29 +221,119 @@ pub fn generate_traceparent() -> Option<String> {
     });
 }
 
+/// Generate a [`TRACESTATE`] string for propagation from the active span's
+/// tracestate, so a vendor chain picked up via [`read_tracestate`] survives
+/// across shell-script hops.
+pub fn generate_tracestate() -> Option<String> {
+    opentelemetry::trace::get_active_span(|span| {
+        let span_context = span.span_context();
+        if span_context.is_valid() {
+            return Some(span_context.trace_state().header());
+        }
+        None
+    })
+}
+
 /// A super-duper opinionated way to initialize otel tracing.
 /// We will respect an existing OTEL_SERVICE_NAME environment variable,
 /// but if it's absent, we set it based on what was passed in the call.
+///
+/// Picks a [`CollectorKind`] from the [`OTEL_COLLECTOR_KIND`] environment
+/// variable (defaulting to [`CollectorKind::Otlp`]); see [`init_with_kind`]
+/// to choose one explicitly instead.
 pub fn init(
     name: &'static str,
 ) -> Result<
     Option<init_tracing_opentelemetry::tracing_subscriber_ext::TracingGuard>,
     Box<dyn std::error::Error>,
+> {
+    init_with_kind(name, exporter::collector_kind_from_env())
+}
+
+/// Like [`init`], but with the exporter backend chosen explicitly rather
+/// than read from the environment. Useful for unit tests and offline
+/// development, where [`CollectorKind::NoWrite`] gives deterministic runs
+/// without a collector, and [`CollectorKind::Stdout`]/[`CollectorKind::Stderr`]
+/// give human-readable local output.
+pub fn init_with_kind(
+    name: &'static str,
+    kind: exporter::CollectorKind,
+) -> Result<
+    Option<init_tracing_opentelemetry::tracing_subscriber_ext::TracingGuard>,
+    Box<dyn std::error::Error>,
 > {
     match std::env::var("OTEL_SERVICE_NAME") {
         Ok(_) => (),
         Err(_) => std::env::set_var("OTEL_SERVICE_NAME", name),
     };
 
-    if let Ok(guard) = init_tracing_opentelemetry::tracing_subscriber_ext::init_subscribers() {
-        return Ok(Some(guard));
-    } else {
-        // Recreate the "temporary subscriber" setup from init-tracing-opentelemtry as a fallback
-        let subscriber = tracing_subscriber::registry()
-            .with(init_tracing_opentelemetry::tracing_subscriber_ext::build_loglevel_filter_layer())
-            .with(init_tracing_opentelemetry::tracing_subscriber_ext::build_logger_text());
-        tracing::subscriber::set_global_default(subscriber)?;
-        tracing::warn!("Tracing setup failed. Falling back to local logging.");
+    opentelemetry::global::set_text_map_propagator(propagation::propagator_from_env());
+
+    if kind == exporter::CollectorKind::Otlp {
+        if let Ok(guard) = init_tracing_opentelemetry::tracing_subscriber_ext::init_subscribers() {
+            return Ok(Some(guard));
+        } else {
+            // Recreate the "temporary subscriber" setup from init-tracing-opentelemtry as a fallback
+            let subscriber = tracing_subscriber::registry()
+                .with(init_tracing_opentelemetry::tracing_subscriber_ext::build_loglevel_filter_layer())
+                .with(init_tracing_opentelemetry::tracing_subscriber_ext::build_logger_text());
+            tracing::subscriber::set_global_default(subscriber)?;
+            tracing::warn!("Tracing setup failed. Falling back to local logging.");
+        }
+        return Ok(None);
     }
+
+    let tracer = exporter::tracer_for_kind(name, kind);
+    let subscriber = tracing_subscriber::registry()
+        .with(init_tracing_opentelemetry::tracing_subscriber_ext::build_loglevel_filter_layer())
+        .with(tracing_opentelemetry::layer().with_tracer(tracer));
+    tracing::subscriber::set_global_default(subscriber)?;
     Ok(None)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_tracestate_parses_key_value_pairs() {
+        std::env::set_var(TRACESTATE, "rojo=00f067aa0ba902b7,congo=t61rcWkgMzE");
+        let ts = read_tracestate();
+        assert_eq!(ts.get("rojo"), Some("00f067aa0ba902b7"));
+        assert_eq!(ts.get("congo"), Some("t61rcWkgMzE"));
+        std::env::remove_var(TRACESTATE);
+    }
+
+    #[test]
+    fn read_tracestate_defaults_to_empty_when_unset() {
+        std::env::remove_var(TRACESTATE);
+        assert_eq!(read_tracestate(), opentelemetry::trace::TraceState::NONE);
+    }
+
+    #[test]
+    fn as_spancontext_honors_the_unsampled_flag() {
+        let tp = traceparent::parse("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-00").unwrap();
+        assert!(!tp.as_spancontext().is_sampled());
+    }
+
+    #[test]
+    fn as_spancontext_honors_the_sampled_flag() {
+        let tp = traceparent::parse("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01").unwrap();
+        assert!(tp.as_spancontext().is_sampled());
+    }
+
+    #[test]
+    fn span_link_carries_supplied_attributes() {
+        let span_context = opentelemetry::trace::SpanContext::new(
+            opentelemetry::trace::TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap(),
+            opentelemetry::trace::SpanId::from_hex("00f067aa0ba902b7").unwrap(),
+            opentelemetry::trace::TraceFlags::SAMPLED,
+            false,
+            opentelemetry::trace::TraceState::NONE,
+        );
+        let attrs = vec![opentelemetry::KeyValue::new("link.type", "follows_from")];
+        let link = span_link(span_context.clone(), attrs.clone());
+        assert_eq!(link.span_context, span_context);
+        assert_eq!(link.attributes, attrs);
+    }
+}
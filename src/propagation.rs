@@ -0,0 +1,155 @@
+//! Extract/inject helpers for carrying trace context through arbitrary
+//! carriers (HTTP/gRPC header maps, message metadata, etc.) rather than just
+//! the [`crate::TRACEPARENT`] environment variable, plus selection of which
+//! wire formats to speak.
+
+use opentelemetry::propagation::{Extractor, Injector, TextMapCompositePropagator, TextMapPropagator};
+use opentelemetry_sdk::propagation::{BaggagePropagator, TraceContextPropagator};
+
+/// Pull the active [`opentelemetry::Context`] out of `carrier` using the
+/// globally configured text map propagator (see [`crate::init`]).
+///
+/// Use this in request handlers (axum, tonic, ...) to parent a new span off
+/// whatever trace context the caller sent in, e.g. an incoming `traceparent`
+/// header.
+pub fn extract_context<C: Extractor>(carrier: &C) -> opentelemetry::Context {
+    opentelemetry::global::get_text_map_propagator(|propagator| propagator.extract(carrier))
+}
+
+/// Push the currently active [`opentelemetry::Context`] into `carrier` using
+/// the globally configured text map propagator (see [`crate::init`]).
+///
+/// Use this before making an outbound request so the callee can pick the
+/// trace context back up with [`extract_context`].
+pub fn inject_context<C: Injector>(carrier: &mut C) {
+    let cx = opentelemetry::Context::current();
+    opentelemetry::global::get_text_map_propagator(|propagator| propagator.inject_context(&cx, carrier))
+}
+
+/// The expected environment variable for selecting one or more propagation
+/// formats, per the [spec's comma-separated list](https://opentelemetry.io/docs/specs/otel/configuration/sdk-environment-variables/#general-sdk-configuration)
+/// (e.g. `tracecontext,b3,xray`).
+pub static OTEL_PROPAGATORS: &str = "OTEL_PROPAGATORS";
+
+/// A wire format [`init`](crate::init) can configure the global text map
+/// propagator to extract/inject, selected via [`OTEL_PROPAGATORS`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PropagationFormat {
+    /// W3C Trace Context (`traceparent`/`tracestate` headers)
+    TraceContext,
+    /// W3C Baggage (`baggage` header)
+    Baggage,
+    /// Zipkin B3, single-header variant (`b3: {trace-id}-{span-id}-{flags}`)
+    B3,
+    /// Zipkin B3, multi-header variant (`X-B3-TraceId`, `X-B3-SpanId`, ...)
+    B3Multi,
+    /// Jaeger's `uber-trace-id` header
+    Jaeger,
+    /// AWS X-Ray's `X-Amzn-Trace-Id` header
+    XRay,
+}
+
+impl PropagationFormat {
+    fn into_propagator(self) -> Box<dyn TextMapPropagator + Send + Sync> {
+        match self {
+            PropagationFormat::TraceContext => Box::new(TraceContextPropagator::new()),
+            PropagationFormat::Baggage => Box::new(BaggagePropagator::new()),
+            PropagationFormat::B3 => Box::new(opentelemetry_zipkin::Propagator::with_encoding(
+                opentelemetry_zipkin::B3Encoding::SingleHeader,
+            )),
+            PropagationFormat::B3Multi => Box::new(opentelemetry_zipkin::Propagator::with_encoding(
+                opentelemetry_zipkin::B3Encoding::MultipleHeader,
+            )),
+            PropagationFormat::Jaeger => Box::new(opentelemetry_jaeger_propagator::Propagator::new()),
+            PropagationFormat::XRay => Box::new(opentelemetry_aws::trace::XrayPropagator::default()),
+        }
+    }
+}
+
+/// Parse the spec's comma-separated propagator list (e.g. `tracecontext,b3,xray`),
+/// silently skipping names we don't recognize.
+fn parse_propagators(val: &str) -> Vec<PropagationFormat> {
+    val.split(',')
+        .filter_map(|name| match name.trim() {
+            "tracecontext" => Some(PropagationFormat::TraceContext),
+            "baggage" => Some(PropagationFormat::Baggage),
+            "b3" => Some(PropagationFormat::B3),
+            "b3multi" => Some(PropagationFormat::B3Multi),
+            "jaeger" => Some(PropagationFormat::Jaeger),
+            "xray" => Some(PropagationFormat::XRay),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Build the [`TextMapCompositePropagator`] [`init`](crate::init) installs
+/// globally, selecting formats from the [`OTEL_PROPAGATORS`] environment
+/// variable if set, and otherwise defaulting to W3C trace context + baggage
+/// per the OTel spec default.
+pub fn propagator_from_env() -> TextMapCompositePropagator {
+    let formats = match std::env::var(OTEL_PROPAGATORS) {
+        Ok(val) => parse_propagators(&val),
+        Err(_) => vec![PropagationFormat::TraceContext, PropagationFormat::Baggage],
+    };
+    TextMapCompositePropagator::new(
+        formats
+            .into_iter()
+            .map(PropagationFormat::into_propagator)
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::trace::TraceContextExt;
+
+    #[test]
+    fn extract_and_inject_round_trip_through_a_carrier() {
+        opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+
+        let span_context = opentelemetry::trace::SpanContext::new(
+            opentelemetry::trace::TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap(),
+            opentelemetry::trace::SpanId::from_hex("00f067aa0ba902b7").unwrap(),
+            opentelemetry::trace::TraceFlags::SAMPLED,
+            false,
+            opentelemetry::trace::TraceState::NONE,
+        );
+        let _guard = opentelemetry::Context::current()
+            .with_remote_span_context(span_context.clone())
+            .attach();
+
+        let mut carrier = std::collections::HashMap::new();
+        inject_context(&mut carrier);
+        assert!(carrier.contains_key("traceparent"));
+
+        let extracted = extract_context(&carrier);
+        assert_eq!(extracted.span().span_context().trace_id(), span_context.trace_id());
+        assert_eq!(extracted.span().span_context().span_id(), span_context.span_id());
+    }
+
+    #[test]
+    fn parse_propagators_recognizes_all_names_and_skips_unknown() {
+        assert_eq!(
+            parse_propagators("tracecontext,baggage,b3,b3multi,jaeger,xray,bogus"),
+            vec![
+                PropagationFormat::TraceContext,
+                PropagationFormat::Baggage,
+                PropagationFormat::B3,
+                PropagationFormat::B3Multi,
+                PropagationFormat::Jaeger,
+                PropagationFormat::XRay,
+            ]
+        );
+    }
+
+    #[test]
+    fn b3_and_b3multi_use_distinct_header_encodings() {
+        let b3_fields = PropagationFormat::B3.into_propagator().fields().collect::<Vec<_>>();
+        let b3_multi_fields = PropagationFormat::B3Multi
+            .into_propagator()
+            .fields()
+            .collect::<Vec<_>>();
+        assert_ne!(b3_fields, b3_multi_fields);
+    }
+}